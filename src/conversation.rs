@@ -0,0 +1,50 @@
+// Multi-turn state for `ask_bedrock`. Without this every call is a standalone question;
+// owning the turn history here lets the Converse API's `.messages(...)` carry the whole
+// conversation instead of just the latest question.
+
+use anyhow::Result;
+use aws_sdk_bedrockruntime::types::{ContentBlock, ConversationRole, Message};
+
+use crate::captioner::Image;
+use crate::converse::build_user_message;
+
+#[derive(Debug, Default)]
+pub struct Conversation {
+    messages: Vec<Message>,
+}
+
+impl Conversation {
+    pub fn new() -> Conversation {
+        Conversation::default()
+    }
+
+    pub fn push_user(&mut self, question: &str, image: Option<&Image>) -> Result<()> {
+        self.messages.push(build_user_message(question, image)?);
+        Ok(())
+    }
+
+    // Builds the message list a new user turn would produce, without committing it to the
+    // conversation. Callers should only call `push_user`/`push_assistant` once the model call
+    // this feeds actually succeeds, so a failed call doesn't leave a dangling user turn with
+    // no assistant reply (which would break the API's alternating-roles requirement on the
+    // next call).
+    pub fn with_pending_user(&self, question: &str, image: Option<&Image>) -> Result<Vec<Message>> {
+        let mut messages = self.messages.clone();
+        messages.push(build_user_message(question, image)?);
+        Ok(messages)
+    }
+
+    pub fn push_assistant(&mut self, text: &str) {
+        self.messages.push(
+            Message::builder()
+                .role(ConversationRole::Assistant)
+                .content(ContentBlock::Text(text.to_string()))
+                .build()
+                .expect("assistant message always has a role and content"),
+        );
+    }
+
+    pub fn messages(&self) -> Vec<Message> {
+        self.messages.clone()
+    }
+}
@@ -0,0 +1,220 @@
+// Converse-based call path, used instead of hand-building per-model `invoke_model` bodies.
+
+use anyhow::{anyhow, Result};
+use aws_sdk_bedrockruntime::primitives::Blob;
+use aws_sdk_bedrockruntime::types::{
+    ContentBlock, ConversationRole, ConverseStreamOutput, ImageBlock, ImageFormat, ImageSource,
+    InferenceConfiguration, Message, StopReason, ToolConfiguration, ToolResultBlock,
+    ToolResultContentBlock,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::io::{self, Write};
+
+use crate::captioner::Image;
+use crate::models::{model_family, ConverseConfig, ModelConfigs, ModelFamily};
+use crate::tools::ToolRegistry;
+
+// Guards against a misbehaving model looping on tool calls forever.
+const MAX_TOOL_ITERATIONS: u8 = 8;
+
+pub fn inference_config_from(cfg: &ConverseConfig) -> InferenceConfiguration {
+    InferenceConfiguration::builder()
+        .max_tokens(cfg.max_tokens)
+        .temperature(cfg.temperature)
+        .top_p(cfg.top_p)
+        .build()
+}
+
+// Picks the defaults section for `model_id`'s family, falling back to `converse` defaults.
+pub fn inference_config_for(model_id: &str, cfg: &ModelConfigs) -> InferenceConfiguration {
+    match model_family(model_id) {
+        ModelFamily::Llama => InferenceConfiguration::builder()
+            .max_tokens(cfg.llama.max_gen_len)
+            .temperature(cfg.llama.temperature)
+            .top_p(cfg.llama.top_p)
+            .build(),
+        ModelFamily::Mistral => InferenceConfiguration::builder()
+            .max_tokens(cfg.mistral.max_tokens)
+            .temperature(cfg.mistral.temperature)
+            .top_p(cfg.mistral.top_p)
+            .build(),
+        ModelFamily::Claude | ModelFamily::Other => inference_config_from(&cfg.converse),
+    }
+}
+
+// Mistral's `top_k` has no `InferenceConfiguration` field, so it goes through here instead.
+pub fn additional_fields_for(model_id: &str, cfg: &ModelConfigs) -> Option<aws_smithy_types::Document> {
+    match model_family(model_id) {
+        ModelFamily::Mistral => Some(aws_smithy_types::Document::from(serde_json::json!({
+            "top_k": cfg.mistral.top_k
+        }))),
+        _ => None,
+    }
+}
+
+fn image_format(extension: &str) -> Result<ImageFormat> {
+    match extension {
+        "png" => Ok(ImageFormat::Png),
+        "jpg" | "jpeg" => Ok(ImageFormat::Jpeg),
+        "gif" => Ok(ImageFormat::Gif),
+        "webp" => Ok(ImageFormat::Webp),
+        other => Err(anyhow!("Unsupported image extension for Converse: {other}")),
+    }
+}
+
+// Build a user `Message` from `question` and an optional image. Used both for a standalone
+// call and for each user turn of a `Conversation`.
+pub fn build_user_message(question: &str, image: Option<&Image>) -> Result<Message> {
+    let mut content = vec![ContentBlock::Text(question.to_string())];
+
+    if let Some(image) = image {
+        let bytes = STANDARD.decode(&image.base64)?;
+        content.push(ContentBlock::Image(
+            ImageBlock::builder()
+                .format(image_format(&image.extension)?)
+                .source(ImageSource::Bytes(Blob::new(bytes)))
+                .build()?,
+        ));
+    }
+
+    Ok(Message::builder()
+        .role(ConversationRole::User)
+        .set_content(Some(content))
+        .build()?)
+}
+
+// Pull the assistant `Message` out of a Converse response.
+fn extract_message(
+    output: &aws_sdk_bedrockruntime::operation::converse::ConverseOutput,
+) -> Result<Message> {
+    output
+        .output()
+        .and_then(|o| o.as_message().ok())
+        .cloned()
+        .ok_or_else(|| anyhow!("Converse response did not contain a message"))
+}
+
+fn extract_text(message: &Message) -> Result<String> {
+    message
+        .content()
+        .iter()
+        .find_map(|block| block.as_text().ok().map(ToString::to_string))
+        .ok_or_else(|| anyhow!("Converse response did not contain a text content block"))
+}
+
+pub async fn call_converse(
+    client: &aws_sdk_bedrockruntime::Client,
+    model_id: &str,
+    messages: Vec<Message>,
+    inference_config: InferenceConfiguration,
+    additional_fields: Option<aws_smithy_types::Document>,
+) -> Result<String> {
+    let response = client
+        .converse()
+        .model_id(model_id)
+        .set_messages(Some(messages))
+        .inference_config(inference_config)
+        .set_additional_model_request_fields(additional_fields)
+        .send()
+        .await?;
+
+    let text = extract_text(&extract_message(&response)?)?;
+    println!("{}", text);
+    Ok(text)
+}
+
+// Drives the tool-use loop, dispatching each `ToolUse` block and feeding the result back
+// until the model stops requesting tools (or the iteration guard trips).
+pub async fn call_converse_with_tools(
+    client: &aws_sdk_bedrockruntime::Client,
+    model_id: &str,
+    mut messages: Vec<Message>,
+    inference_config: InferenceConfiguration,
+    additional_fields: Option<aws_smithy_types::Document>,
+    tools: &ToolRegistry,
+) -> Result<String> {
+    let tool_config: ToolConfiguration = tools.tool_config()?;
+
+    for _ in 0..MAX_TOOL_ITERATIONS {
+        let response = client
+            .converse()
+            .model_id(model_id)
+            .set_messages(Some(messages.clone()))
+            .inference_config(inference_config.clone())
+            .set_additional_model_request_fields(additional_fields.clone())
+            .tool_config(tool_config.clone())
+            .send()
+            .await?;
+
+        let assistant_message = extract_message(&response)?;
+        messages.push(assistant_message.clone());
+
+        if !matches!(response.stop_reason(), StopReason::ToolUse) {
+            return extract_text(&assistant_message);
+        }
+
+        let mut result_content = Vec::new();
+        for block in assistant_message.content() {
+            if let ContentBlock::ToolUse(tool_use) = block {
+                let input: serde_json::Value =
+                    serde_json::to_value(tool_use.input().clone()).unwrap_or(serde_json::Value::Null);
+                let result = tools.dispatch(tool_use.name(), input).await;
+                let (content, status) = match result {
+                    Ok(text) => (text, aws_sdk_bedrockruntime::types::ToolResultStatus::Success),
+                    Err(e) => (e.to_string(), aws_sdk_bedrockruntime::types::ToolResultStatus::Error),
+                };
+                result_content.push(ContentBlock::ToolResult(
+                    ToolResultBlock::builder()
+                        .tool_use_id(tool_use.tool_use_id())
+                        .content(ToolResultContentBlock::Text(content))
+                        .status(status)
+                        .build()?,
+                ));
+            }
+        }
+
+        messages.push(
+            Message::builder()
+                .role(ConversationRole::User)
+                .set_content(Some(result_content))
+                .build()?,
+        );
+    }
+
+    Err(anyhow!(
+        "Model kept requesting tool calls past {MAX_TOOL_ITERATIONS} iterations"
+    ))
+}
+
+pub async fn call_converse_stream(
+    client: &aws_sdk_bedrockruntime::Client,
+    model_id: &str,
+    messages: Vec<Message>,
+    inference_config: InferenceConfiguration,
+    additional_fields: Option<aws_smithy_types::Document>,
+) -> Result<String> {
+    let mut response = client
+        .converse_stream()
+        .model_id(model_id)
+        .set_messages(Some(messages))
+        .inference_config(inference_config)
+        .set_additional_model_request_fields(additional_fields)
+        .send()
+        .await?;
+
+    let mut output = String::new();
+
+    while let Some(event) = response.stream.recv().await? {
+        if let ConverseStreamOutput::ContentBlockDelta(delta_event) = event {
+            if let Some(delta) = delta_event.delta() {
+                if let Ok(text) = delta.as_text() {
+                    output.push_str(text);
+                    print!("{}", text);
+                    io::stdout().flush()?;
+                }
+            }
+        }
+    }
+    println!();
+    Ok(output)
+}
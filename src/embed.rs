@@ -0,0 +1,108 @@
+// Embeddings subsystem. Separate from `ask_bedrock`/`converse` because embedding models
+// are invoked for a vector, not a chat turn, and go through `invoke_model` directly rather
+// than Converse (which is chat-message shaped and doesn't front embedding models).
+
+use anyhow::{anyhow, Result};
+use aws_sdk_bedrockruntime::primitives::Blob;
+use serde::Deserialize;
+use serde_json::json;
+
+#[derive(Debug, Deserialize)]
+struct TitanEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CohereEmbeddingResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+// Cohere's embed models cap the `texts` array at 96 entries per request.
+const COHERE_MAX_BATCH_SIZE: usize = 96;
+
+// Titan only ever takes one input per call; Cohere takes a batch in one `texts` array.
+fn request_body(model_id: &str, inputs: &[String]) -> Result<Blob> {
+    let body = match model_id {
+        "amazon.titan-embed-text-v1" => {
+            let input = inputs
+                .first()
+                .ok_or_else(|| anyhow!("Titan embedding request needs at least one input"))?;
+            json!({ "inputText": input })
+        }
+        "cohere.embed-english-v3" => json!({
+            "texts": inputs,
+            "input_type": "search_document",
+        }),
+        other => return Err(anyhow!("Unknown embedding model: {other}")),
+    };
+    Ok(Blob::new(serde_json::to_vec(&body)?))
+}
+
+fn parse_embeddings(model_id: &str, payload_bytes: &[u8]) -> Result<Vec<Vec<f32>>> {
+    match model_id {
+        "amazon.titan-embed-text-v1" => {
+            Ok(vec![serde_json::from_slice::<TitanEmbeddingResponse>(payload_bytes)?.embedding])
+        }
+        "cohere.embed-english-v3" => {
+            Ok(serde_json::from_slice::<CohereEmbeddingResponse>(payload_bytes)?.embeddings)
+        }
+        other => Err(anyhow!("Unknown embedding model: {other}")),
+    }
+}
+
+async fn invoke(
+    client: &aws_sdk_bedrockruntime::Client,
+    model_id: &str,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    let response = client
+        .invoke_model()
+        .body(request_body(model_id, inputs)?)
+        .content_type("application/json")
+        .accept("*/*")
+        .model_id(model_id)
+        .send()
+        .await?;
+
+    parse_embeddings(model_id, response.body.as_ref())
+}
+
+// Embed a single piece of text into a vector using `model_id`
+// (`amazon.titan-embed-text-v1` or `cohere.embed-english-v3`).
+pub async fn embed_text(
+    client: &aws_sdk_bedrockruntime::Client,
+    model_id: &str,
+    input: &str,
+) -> Result<Vec<f32>> {
+    invoke(client, model_id, &[input.to_string()])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Embedding response contained no vectors"))
+}
+
+// Embed a batch of texts. Cohere accepts a batch of `texts` per request (chunked at
+// `COHERE_MAX_BATCH_SIZE`); Titan only takes one input per call, so those fall back to
+// one call per text.
+pub async fn embed_texts(
+    client: &aws_sdk_bedrockruntime::Client,
+    model_id: &str,
+    inputs: &[String],
+) -> Result<Vec<Vec<f32>>> {
+    match model_id {
+        "cohere.embed-english-v3" => {
+            let mut embeddings = Vec::with_capacity(inputs.len());
+            for chunk in inputs.chunks(COHERE_MAX_BATCH_SIZE) {
+                embeddings.extend(invoke(client, model_id, chunk).await?);
+            }
+            Ok(embeddings)
+        }
+        _ => {
+            let mut embeddings = Vec::with_capacity(inputs.len());
+            for input in inputs {
+                embeddings.push(embed_text(client, model_id, input).await?);
+            }
+            Ok(embeddings)
+        }
+    }
+}
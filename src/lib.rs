@@ -1,26 +1,34 @@
 pub mod captioner;
+pub mod conversation;
+pub mod converse;
+pub mod embed;
 pub mod models;
+pub mod tools;
 pub mod utils;
 
 use anyhow::anyhow;
 use aws_config::meta::region::RegionProviderChain;
 use aws_config::BehaviorVersion;
 use aws_types::region::Region;
-use core::panic;
 use serde::ser::Error;
-use serde::Deserialize;
-use serde_json::Value;
-use std::{env, io};
+use std::env;
 
 use anyhow::Result;
 
 use aws_sdk_bedrockruntime::primitives::Blob;
-use aws_sdk_bedrockruntime::types::ResponseStream;
 
 use models::claudev3::{ClaudeImageSource, ClaudeV3Body, ClaudeV3Response};
 
+use conversation::Conversation;
+use converse::{
+    additional_fields_for, build_user_message, call_converse, call_converse_stream,
+    call_converse_with_tools, inference_config_for,
+};
+use models::check_for_multimodality;
 use models::check_for_streaming;
+use models::input_modalities;
 use models::load_config;
+use tools::ToolRegistry;
 
 
 
@@ -47,6 +55,8 @@ pub async fn configure_aws(s: String) -> aws_config::SdkConfig {
 pub enum RunType {
     Standard,
     Captioning,
+    // Carries the tools the model is allowed to call for this turn.
+    ToolUse(ToolRegistry),
 }
 
 #[derive(Debug)]
@@ -157,8 +167,13 @@ fn q_to_bcs_with_defaults(
             })
         }
 
-        
-        &_ => todo!(),
+
+        // Vision-capable models pass `check_for_multimodality` generically, but captioning
+        // still needs a model-specific body builder here; fail cleanly instead of panicking
+        // until one is added for this model.
+        &_ => Err(anyhow!(
+            "No captioning request body is implemented for model `{model_id}` yet"
+        )),
     }
 }
 
@@ -173,8 +188,11 @@ fn mk_bedrock_call(
     bcs_to_bedrock_call(bcs)
 }
 
-// Given a question and model_id, create and execute a call to bedrock.
+// Given a question and model_id, create and execute a call to bedrock. When `conversation`
+// is given, the full turn history is submitted instead of just `question`, and the model's
+// reply is appended back onto it so the next call keeps context.
 // This will fail if model_id is not known to q_to_bcs_with_defaults
+// (Captioning only; Standard and ToolUse go through `converse` and do not use it).
 pub async fn ask_bedrock(
     question: &String,
     image: Option<&Image>,
@@ -182,32 +200,75 @@ pub async fn ask_bedrock(
     run_type: RunType,
     client: &aws_sdk_bedrockruntime::Client,
     bedrock_client: &aws_sdk_bedrock::Client,
+    mut conversation: Option<&mut Conversation>,
 ) -> Result<String, anyhow::Error> {
     match run_type {
         RunType::Standard => {
-            let bcall = mk_bedrock_call(question, image, model_id)?;
+            let model_defaults = load_config(String::from("model_config.ron"))?;
+            let inference_config = inference_config_for(model_id, &model_defaults);
+            let additional_fields = additional_fields_for(model_id, &model_defaults);
+
+            let messages = match conversation.as_deref() {
+                Some(conversation) => conversation.with_pending_user(question, image)?,
+                None => vec![build_user_message(question, image)?],
+            };
+
             // check if model supports streaming:
-            if check_for_streaming(model_id.to_string(), bedrock_client).await? {
-                let response = call_bedrock_stream(client, bcall).await?;
-                Ok(response)
+            let response = if check_for_streaming(model_id.to_string(), bedrock_client).await? {
+                call_converse_stream(client, model_id, messages, inference_config, additional_fields).await
             } else {
                 // if it does not just call it
-                let response = call_bedrock(client, bcall, run_type).await?;
-                Ok(response)
+                call_converse(client, model_id, messages, inference_config, additional_fields).await
+            }?;
+
+            // Only commit the turn once the call has actually succeeded, so a failed call
+            // doesn't leave a dangling user turn with no assistant reply.
+            if let Some(conversation) = conversation {
+                conversation.push_user(question, image)?;
+                conversation.push_assistant(&response);
             }
+            Ok(response)
+        }
+        RunType::ToolUse(tools) => {
+            let model_defaults = load_config(String::from("model_config.ron"))?;
+            let inference_config = inference_config_for(model_id, &model_defaults);
+            let additional_fields = additional_fields_for(model_id, &model_defaults);
+
+            let messages = match conversation.as_deref() {
+                Some(conversation) => conversation.with_pending_user(question, image)?,
+                None => vec![build_user_message(question, image)?],
+            };
+
+            let response = call_converse_with_tools(
+                client,
+                model_id,
+                messages,
+                inference_config,
+                additional_fields,
+                &tools,
+            )
+            .await?;
+
+            // Only commit the turn once the call has actually succeeded, so a failed call
+            // doesn't leave a dangling user turn with no assistant reply.
+            if let Some(conversation) = conversation {
+                conversation.push_user(question, image)?;
+                conversation.push_assistant(&response);
+            }
+            Ok(response)
         }
         RunType::Captioning => {
             if image.is_some() {
-                // TODO: Programmaticall check for multimodality of FMs
-                if model_id != "anthropic.claude-3-sonnet-20240229-v1:0"
-                    && model_id != "anthropic.claude-3-haiku-20240307-v1:0"
-                {
-                    eprintln!("🛑SORRY! The model you selected is not able to caption images. Please select either `claude-v3-sonnet` or `claude-v3-haiku`.");
-                    std::process::exit(1);
+                if !check_for_multimodality(model_id.to_string(), bedrock_client).await? {
+                    let modalities = input_modalities(model_id.to_string(), bedrock_client).await?;
+                    return Err(anyhow!(
+                        "The model `{model_id}` does not accept image input (supported input modalities: {:?}). Please select a vision-capable model.",
+                        modalities
+                    ));
                 }
                 let bcall = mk_bedrock_call(question, image, model_id)?;
                 // because this is captioniong, we dont need streaming
-                let caption = call_bedrock(client, bcall, run_type).await?;
+                let caption = call_bedrock(client, bcall).await?;
                 Ok(caption)
             } else {
                 Err(anyhow!(
@@ -221,57 +282,22 @@ pub async fn ask_bedrock(
 
 //========================================
 
-fn process_response(
-    model_id: &str,
-    payload_bytes: &[u8],
-    streaming: bool,
-) -> Result<String, serde_json::Error> {
-    if !streaming {
-        match model_id {
-            "anthropic.claude-3-sonnet-20240229-v1:0"
-            | "anthropic.claude-3-haiku-20240307-v1:0" => {
-                serde_json::from_slice::<ClaudeV3Response>(payload_bytes)
-                    .map(|res| res.content[0].text.clone())
-            }
-            &_ => Err(serde_json::Error::custom("Unknown model ID")),
-        }
-    } else {
-        match model_id {
-            "anthropic.claude-3-sonnet-20240229-v1:0"
-            | "anthropic.claude-3-haiku-20240307-v1:0" => {
-                // NOTE: ClaudeV3 is complicated and the streamed response is not always the same
-                // this means we need to check for specific fields in the response and then return only
-                // if we have the type of response set to "text_delta"
-                // FIX: I feel like this could be way better
-                // FIX: Make it so you check for other message types and to something about it.
-                let mut deserializer = serde_json::Deserializer::from_slice(payload_bytes);
-                let value = Value::deserialize(&mut deserializer)?;
-                if let Value::Object(obj) = value {
-                    if let Some(Value::Object(delta)) = obj.get("delta") {
-                        if let Some(Value::String(delta_type)) = delta.get("type") {
-                            if delta_type == "text_delta" {
-                                let text = delta
-                                    .get("text")
-                                    .and_then(|v| v.as_str().map(ToString::to_string))
-                                    .ok_or_else(|| Error::custom("text"))?;
-                                return Ok(text);
-                            }
-                        }
-                    }
-                }
-                Ok(String::from(""))
-            }
-            &_ => Err(serde_json::Error::custom("Unknown model ID")),
+// Only reached by the Captioning run type now; Standard calls go through `converse`.
+fn process_response(model_id: &str, payload_bytes: &[u8]) -> Result<String, serde_json::Error> {
+    match model_id {
+        "anthropic.claude-3-sonnet-20240229-v1:0"
+        | "anthropic.claude-3-haiku-20240307-v1:0" => {
+            serde_json::from_slice::<ClaudeV3Response>(payload_bytes)
+                .map(|res| res.content[0].text.clone())
         }
+        &_ => Err(serde_json::Error::custom("Unknown model ID")),
     }
 }
 
-// this function is only called if we do not want the streaming result back.
-// so far this is here only for models that do not support streaming (ie Jurrasic2Ultra)
+// Captioning never streams, so this always goes through `invoke_model`.
 async fn call_bedrock(
     bc: &aws_sdk_bedrockruntime::Client,
     c: BedrockCall,
-    run_type: RunType,
 ) -> Result<String, anyhow::Error> {
     let response = bc
         .invoke_model()
@@ -282,50 +308,6 @@ async fn call_bedrock(
         .send()
         .await?;
 
-    let response_text = process_response(c.model_id.as_str(), response.body.as_ref(), false);
-    match response_text {
-        Ok(text) => match run_type {
-            RunType::Captioning => Ok(text),
-            RunType::Standard => {
-                println!("{}", text);
-                Ok(text)
-            }
-        },
-        Err(e) => Err(anyhow!("Error processing response: {}", e)),
-    }
-}
-
-async fn call_bedrock_stream(bc: &aws_sdk_bedrockruntime::Client, c: BedrockCall) -> Result<String, anyhow::Error> {
-    let mut resp = bc
-        .invoke_model_with_response_stream()
-        .body(c.body)
-        .content_type(c.content_type)
-        .accept(c.accept)
-        .model_id(&c.model_id)
-        .send()
-        .await?;
-
-    let mut output = String::new();
-
-    while let Some(event) = resp.body.recv().await? {
-        match event {
-            ResponseStream::Chunk(payload_part) => {
-                if let Some(payload_bytes) = payload_part.bytes {
-                    let response_text =
-                        process_response(c.model_id.as_str(), payload_bytes.as_ref(), true);
-                    match response_text {
-                        Ok(text) => {
-                            output.push_str(&text);
-                            print!("{}", &text);
-                            io::stdout().flush()?;
-                        }
-                        Err(e) => eprintln!("Error processing response: {}", e),
-                    }
-                }
-            }
-            otherwise => panic!("received unexpected event type: {:?}", otherwise),
-        }
-    }
-    println!();
-    Ok(output)
+    process_response(c.model_id.as_str(), response.body.as_ref())
+        .map_err(|e| anyhow!("Error processing response: {}", e))
 }
\ No newline at end of file
@@ -4,14 +4,63 @@ pub mod claudev3;
 use claudev3::ClaudeV3Config;
 
 use anyhow::{anyhow, Result};
-use aws_sdk_bedrock::{self, types::FoundationModelDetails};
+use aws_sdk_bedrock::{self, types::{FoundationModelDetails, ModelModality}};
 use serde::{Deserialize, Serialize};
 
 use std::fs;
 
+// Inference defaults for calls made through the Converse API.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConverseConfig {
+    pub max_tokens: i32,
+    pub temperature: f32,
+    pub top_p: f32,
+}
+
+// `max_gen_len` maps onto Converse's `max_tokens`.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct LlamaConfig {
+    pub max_gen_len: i32,
+    pub temperature: f32,
+    pub top_p: f32,
+}
+
+// `top_k` rides along via `converse::additional_fields_for` instead of a Converse field.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct MistralConfig {
+    pub max_tokens: i32,
+    pub temperature: f32,
+    pub top_p: f32,
+    pub top_k: i32,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub struct ModelConfigs {
-    pub claude_v3: ClaudeV3Config
+    pub claude_v3: ClaudeV3Config,
+    pub converse: ConverseConfig,
+    pub llama: LlamaConfig,
+    pub mistral: MistralConfig,
+}
+
+// Which prompt/response family a `model_id` belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelFamily {
+    Claude,
+    Llama,
+    Mistral,
+    Other,
+}
+
+pub fn model_family(model_id: &str) -> ModelFamily {
+    if model_id.starts_with("anthropic.claude") {
+        ModelFamily::Claude
+    } else if model_id.starts_with("meta.llama3") {
+        ModelFamily::Llama
+    } else if model_id.starts_with("mistral.mistral") || model_id.starts_with("mistral.mixtral") {
+        ModelFamily::Mistral
+    } else {
+        ModelFamily::Other
+    }
 }
 
 pub fn load_config(f: String) -> Result<ModelConfigs> {
@@ -35,4 +84,39 @@ pub async fn check_for_streaming(
         Some(o) => Ok(o),
         None => Ok(false),
     }
+}
+
+// Whether `m` accepts image input, checked against the foundation model's advertised
+// input modalities instead of a hardcoded list of known vision-capable model IDs.
+pub async fn check_for_multimodality(
+    m: String,
+    c: &aws_sdk_bedrock::Client,
+) -> Result<bool, anyhow::Error> {
+    let call = c.get_foundation_model().model_identifier(m);
+    let res = call.send().await;
+    let model_details: FoundationModelDetails = res?
+        .model_details()
+        .ok_or_else(|| anyhow!("Unable to get model details"))?
+        .clone();
+
+    Ok(model_details
+        .input_modalities()
+        .unwrap_or_default()
+        .contains(&ModelModality::Image))
+}
+
+// The full set of input modalities `m` advertises, used to put together a clear error
+// message when a caller tries to send an image to a text-only model.
+pub async fn input_modalities(
+    m: String,
+    c: &aws_sdk_bedrock::Client,
+) -> Result<Vec<ModelModality>, anyhow::Error> {
+    let call = c.get_foundation_model().model_identifier(m);
+    let res = call.send().await;
+    let model_details: FoundationModelDetails = res?
+        .model_details()
+        .ok_or_else(|| anyhow!("Unable to get model details"))?
+        .clone();
+
+    Ok(model_details.input_modalities().unwrap_or_default().to_vec())
 }
\ No newline at end of file
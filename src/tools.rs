@@ -0,0 +1,90 @@
+// Tool/function-calling support for the Converse path.
+
+use anyhow::{anyhow, Result};
+use aws_sdk_bedrockruntime::types::{Tool, ToolConfiguration, ToolInputSchema, ToolSpecification};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+// Description of a single tool the model can choose to call.
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    pub input_schema: Value,
+}
+
+impl ToolSpec {
+    pub fn new(name: impl Into<String>, description: impl Into<String>, input_schema: Value) -> ToolSpec {
+        ToolSpec {
+            name: name.into(),
+            description: description.into(),
+            input_schema,
+        }
+    }
+}
+
+// Runs a tool call and returns the text to send back as its result.
+pub type ToolHandler =
+    Box<dyn Fn(Value) -> Pin<Box<dyn Future<Output = Result<String>> + Send>> + Send + Sync>;
+
+// Tools exposed on a call, keyed by name so a `ToolUse` block can be dispatched back to it.
+#[derive(Default)]
+pub struct ToolRegistry {
+    specs: Vec<ToolSpec>,
+    handlers: HashMap<String, ToolHandler>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> ToolRegistry {
+        ToolRegistry::default()
+    }
+
+    pub fn register(&mut self, spec: ToolSpec, handler: ToolHandler) {
+        self.handlers.insert(spec.name.clone(), handler);
+        self.specs.push(spec);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    // Build the `ToolConfiguration` to attach to a Converse request.
+    pub fn tool_config(&self) -> Result<ToolConfiguration> {
+        let tools = self
+            .specs
+            .iter()
+            .map(|spec| {
+                Ok(Tool::ToolSpec(
+                    ToolSpecification::builder()
+                        .name(&spec.name)
+                        .description(&spec.description)
+                        .input_schema(ToolInputSchema::Json(aws_smithy_types::Document::from(
+                            spec.input_schema.clone(),
+                        )))
+                        .build()?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ToolConfiguration::builder()
+            .set_tools(Some(tools))
+            .build()?)
+    }
+
+    pub async fn dispatch(&self, name: &str, input: Value) -> Result<String> {
+        let handler = self
+            .handlers
+            .get(name)
+            .ok_or_else(|| anyhow!("No handler registered for tool `{name}`"))?;
+        handler(input).await
+    }
+}
+
+impl std::fmt::Debug for ToolRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ToolRegistry")
+            .field("tools", &self.specs.iter().map(|s| &s.name).collect::<Vec<_>>())
+            .finish()
+    }
+}